@@ -0,0 +1,28 @@
+use super::span::{Site, Span};
+
+pub struct Diagnostic;
+
+impl Diagnostic {
+    // Renders a caret-style diagnostic for `span` within `input`, e.g.:
+    //
+    // 1:2: error: Invalid identifier "3div"
+    // <3div>
+    //  ^^^^
+    pub fn render(input: &str, span: Span, message: &str) -> String {
+        let (line_start, line_end) = Site::line_bounds(input, span.start);
+        let line = &input[line_start..line_end];
+
+        let underline_start = input[line_start..span.start].chars().count();
+        let underline_end = span.end.max(span.start + 1).min(line_end);
+        let underline_len = input[span.start..underline_end].chars().count().max(1);
+
+        format!(
+            "{}: error: {}\n{}\n{}{}",
+            Site::describe(input, span.start),
+            message,
+            line,
+            " ".repeat(underline_start),
+            "^".repeat(underline_len)
+        )
+    }
+}
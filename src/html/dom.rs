@@ -20,3 +20,282 @@ pub struct ElementNode {
 
 pub type ElementChildren = Vec<Node>;
 pub type ElementAttributes = HashMap<String, String>;
+
+impl Node {
+    // Depth-first visit of this node and all of its descendants.
+    pub fn walk<F: FnMut(&Node)>(&self, visitor: &mut F) {
+        visitor(self);
+
+        if let Node::Element(element) = self {
+            for child in &element.children {
+                child.walk(visitor);
+            }
+        }
+    }
+
+    pub fn to_html(&self) -> String {
+        match self {
+            Node::Text(text) => escape_text(&text.content),
+            Node::Element(element) => element.to_html(),
+        }
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        match self {
+            Node::Text(text) => format!("(text \"{}\")", escape_sexpr_string(&text.content)),
+            Node::Element(element) => element.to_sexpr(),
+        }
+    }
+}
+
+impl ElementNode {
+    // Concatenates the content of all descendant text nodes, inserting a
+    // space between nodes that come from different elements.
+    pub fn text_content(&self) -> String {
+        let mut content = String::new();
+
+        for child in &self.children {
+            let child_content = match child {
+                Node::Text(text) => text.content.clone(),
+                Node::Element(element) => element.text_content(),
+            };
+
+            if child_content.is_empty() {
+                continue;
+            }
+
+            if !content.is_empty() {
+                content.push(' ');
+            }
+
+            content.push_str(&child_content);
+        }
+
+        content
+    }
+
+    pub fn get_elements_by_tag_name(&self, tag: &str) -> Vec<&ElementNode> {
+        let mut matches = Vec::new();
+
+        self.collect_elements_by_tag_name(tag, &mut matches);
+
+        matches
+    }
+
+    fn collect_elements_by_tag_name<'a>(&'a self, tag: &str, matches: &mut Vec<&'a ElementNode>) {
+        if self.tag_name == tag {
+            matches.push(self);
+        }
+
+        for child in &self.children {
+            if let Node::Element(element) = child {
+                element.collect_elements_by_tag_name(tag, matches);
+            }
+        }
+    }
+
+    pub fn get_element_by_id(&self, id: &str) -> Option<&ElementNode> {
+        if self.attributes.get("id").map(String::as_str) == Some(id) {
+            return Some(self);
+        }
+
+        for child in &self.children {
+            if let Node::Element(element) = child {
+                if let Some(found) = element.get_element_by_id(id) {
+                    return Some(found);
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = format!("<{}", self.tag_name);
+
+        let mut attributes = self.attributes.iter().collect::<Vec<_>>();
+        attributes.sort_by_key(|(name, _)| name.as_str());
+
+        for (name, value) in attributes {
+            html.push_str(&format!(" {}=\"{}\"", name, escape_attribute_value(value)));
+        }
+
+        html.push('>');
+
+        for child in &self.children {
+            html.push_str(&child.to_html());
+        }
+
+        html.push_str(&format!("</{}>", self.tag_name));
+
+        html
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        if self.children.is_empty() {
+            return format!("({})", self.tag_name);
+        }
+
+        let children = self
+            .children
+            .iter()
+            .map(Node::to_sexpr)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!("({} {})", self.tag_name, children)
+    }
+}
+
+// Escapes the characters that would otherwise be misread as markup when
+// `to_html` re-emits this text, so `parse(x).to_html()` stays re-parseable.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+// Escapes the characters that would otherwise end the surrounding quotes
+// early when `to_html` re-emits this attribute value.
+fn escape_attribute_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+// Escapes the characters that would otherwise end the surrounding string
+// literal early when `to_sexpr` re-emits this text.
+fn escape_sexpr_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::parser::Parser;
+
+    #[test]
+    fn to_html_round_trips_entity_bearing_content() {
+        let mut parser = Parser::new("<div title=\"a &quot; b\">1 &gt; 0 &amp; &lt;3</div>");
+        let node = parser.parse().unwrap();
+        let html = node.to_html();
+
+        let mut reparsed = Parser::new(&html);
+        let node_again = reparsed.parse().unwrap();
+
+        assert_eq!(html, node_again.to_html());
+    }
+
+    #[test]
+    fn to_html_escapes_reserved_characters() {
+        let mut parser = Parser::new("<div title=\"a &quot; b\">1 &gt; 0</div>");
+        let node = parser.parse().unwrap();
+        let html = node.to_html();
+
+        assert!(html.contains("&quot;"));
+        assert!(html.contains("&gt;"));
+    }
+
+    #[test]
+    fn to_html_serializes_attributes_in_a_stable_order() {
+        let mut parser = Parser::new("<div a=\"1\" b=\"2\" c=\"3\" d=\"4\" e=\"5\">x</div>");
+        let node = parser.parse().unwrap();
+
+        let first = node.to_html();
+        let mut reparsed = Parser::new(&first);
+        let second = reparsed.parse().unwrap().to_html();
+
+        assert_eq!(first, second);
+        assert_eq!(first, "<div a=\"1\" b=\"2\" c=\"3\" d=\"4\" e=\"5\">x</div>");
+    }
+
+    #[test]
+    fn to_sexpr_escapes_quotes_in_text_content() {
+        let mut parser = Parser::new("<div>has \"quotes\" inside</div>");
+        let node = parser.parse().unwrap();
+
+        assert_eq!(
+            node.to_sexpr(),
+            "(div (text \"has \\\"quotes\\\" inside\"))"
+        );
+    }
+
+    #[test]
+    fn walk_visits_every_node_depth_first() {
+        let mut parser = Parser::new("<div><span>a</span><span>b</span></div>");
+        let node = parser.parse().unwrap();
+
+        let mut tags = Vec::new();
+        node.walk(&mut |visited| {
+            if let Node::Element(element) = visited {
+                tags.push(element.tag_name.clone());
+            }
+        });
+
+        assert_eq!(tags, vec!["div", "span", "span"]);
+    }
+
+    #[test]
+    fn text_content_joins_descendant_text_with_spaces() {
+        let mut parser = Parser::new("<div><span>a</span><span>b</span></div>");
+        let node = parser.parse().unwrap();
+
+        let Node::Element(div) = node else {
+            panic!("expected an element");
+        };
+
+        assert_eq!(div.text_content(), "a b");
+    }
+
+    #[test]
+    fn get_elements_by_tag_name_finds_nested_matches() {
+        let mut parser = Parser::new("<div><span>a</span><p><span>b</span></p></div>");
+        let node = parser.parse().unwrap();
+
+        let Node::Element(div) = node else {
+            panic!("expected an element");
+        };
+
+        assert_eq!(div.get_elements_by_tag_name("span").len(), 2);
+    }
+
+    #[test]
+    fn get_element_by_id_finds_nested_match() {
+        let mut parser = Parser::new("<div><p id=\"target\">a</p></div>");
+        let node = parser.parse().unwrap();
+
+        let Node::Element(div) = node else {
+            panic!("expected an element");
+        };
+
+        let found = div.get_element_by_id("target").unwrap();
+        assert_eq!(found.tag_name, "p");
+    }
+}
@@ -0,0 +1,110 @@
+const NAMED_ENTITIES: &[(&str, char)] = &[
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{00A0}'),
+    ("copy", '\u{00A9}'),
+    ("reg", '\u{00AE}'),
+    ("trade", '\u{2122}'),
+    ("euro", '\u{20AC}'),
+    ("mdash", '\u{2014}'),
+    ("ndash", '\u{2013}'),
+    ("hellip", '\u{2026}'),
+];
+
+// Decodes named (`&amp;`), decimal (`&#169;`) and hex (`&#x2764;`) character
+// references in `input` to their Unicode scalar. An unterminated or
+// unrecognized `&...` is preserved literally rather than dropped.
+pub fn decode(input: &str) -> String {
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::with_capacity(input.len());
+    let mut index = 0;
+
+    while index < chars.len() {
+        if chars[index] != '&' {
+            output.push(chars[index]);
+            index += 1;
+            continue;
+        }
+
+        match decode_reference(&chars[index..]) {
+            Some((decoded, consumed)) => {
+                output.push(decoded);
+                index += consumed;
+            }
+            None => {
+                output.push('&');
+                index += 1;
+            }
+        }
+    }
+
+    output
+}
+
+// Tries to decode a character reference starting at `chars[0]` (the `&`).
+// Returns the decoded character and how many input chars it consumed, or
+// `None` if `chars` doesn't start with a terminated, recognized reference.
+fn decode_reference(chars: &[char]) -> Option<(char, usize)> {
+    let end = chars.iter().position(|char| *char == ';')?;
+    let body: String = chars[1..end].iter().collect();
+    let consumed = end + 1;
+
+    let decoded = match body.strip_prefix('#') {
+        Some(numeric) => decode_numeric_reference(numeric)?,
+        None => named_entity(&body)?,
+    };
+
+    Some((decoded, consumed))
+}
+
+// Numeric references outside the valid Unicode scalar range decode to the
+// replacement character rather than being rejected.
+fn decode_numeric_reference(numeric: &str) -> Option<char> {
+    let (digits, radix) = match numeric.strip_prefix('x').or_else(|| numeric.strip_prefix('X')) {
+        Some(hex) => (hex, 16),
+        None => (numeric, 10),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|char| char.is_digit(radix)) {
+        return None;
+    }
+
+    // Every digit is valid here, so a parse failure can only mean overflow.
+    let scalar = u32::from_str_radix(digits, radix).unwrap_or(u32::MAX);
+
+    Some(char::from_u32(scalar).unwrap_or('\u{FFFD}'))
+}
+
+fn named_entity(name: &str) -> Option<char> {
+    NAMED_ENTITIES
+        .iter()
+        .find(|(entity_name, _)| *entity_name == name)
+        .map(|(_, char)| *char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_handles_named_decimal_and_hex_references() {
+        assert_eq!(decode("a &amp; b"), "a & b");
+        assert_eq!(decode("&#169;"), "\u{00A9}");
+        assert_eq!(decode("&#x2764;"), "\u{2764}");
+    }
+
+    #[test]
+    fn decode_preserves_unterminated_and_unrecognized_references_literally() {
+        assert_eq!(decode("a &amp b"), "a &amp b");
+        assert_eq!(decode("&notareference;"), "&notareference;");
+        assert_eq!(decode("a & b"), "a & b");
+    }
+
+    #[test]
+    fn decode_replaces_out_of_range_numeric_references() {
+        assert_eq!(decode("&#x110000;"), "\u{FFFD}");
+    }
+}
@@ -0,0 +1,314 @@
+use super::parser::{
+    InvalidAttributeValueError, InvalidIdentifierError, ParserError, PrematureEndOfFileError,
+    UnexpectedTokenError,
+};
+use super::span::Span;
+
+const GREATER_THAN: u8 = 0x003E;
+const LESS_THAN: u8 = 0x003C;
+const WHITESPACE: u8 = 0x0020;
+const SLASH: u8 = 0x002F;
+const QUOTE: u8 = 0x0022;
+const EQUALS: u8 = 0x003D;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    TagOpen { name: Span },
+    TagClose { name: Span },
+    TagSelfClose { name: Span },
+    AttributeName(Span),
+    AttributeValue(Span),
+    Text(Span),
+}
+
+impl Token {
+    pub fn span(&self) -> Span {
+        match self {
+            Token::TagOpen { name } => *name,
+            Token::TagClose { name } => *name,
+            Token::TagSelfClose { name } => *name,
+            Token::AttributeName(span) => *span,
+            Token::AttributeValue(span) => *span,
+            Token::Text(span) => *span,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Token::TagOpen { .. } => "tag open",
+            Token::TagClose { .. } => "tag close",
+            Token::TagSelfClose { .. } => "self-closing tag",
+            Token::AttributeName(_) => "attribute name",
+            Token::AttributeValue(_) => "attribute value",
+            Token::Text(_) => "text",
+        }
+    }
+}
+
+pub struct Lexer {
+    input: Vec<u8>,
+    position: usize,
+}
+
+impl Lexer {
+    pub fn new(input: &str) -> Lexer {
+        Lexer {
+            input: input.as_bytes().into(),
+            position: 0,
+        }
+    }
+
+    fn eof(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    fn current_char(&self) -> u8 {
+        self.input[self.position]
+    }
+
+    fn skip_whitespace(&mut self) {
+        while !self.eof() && self.current_char() == WHITESPACE {
+            self.position += 1;
+        }
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, ParserError> {
+        let mut tokens = Vec::new();
+
+        while !self.eof() {
+            match self.current_char() {
+                LESS_THAN => self.lex_tag(&mut tokens)?,
+                WHITESPACE => self.skip_whitespace(),
+                _ => self.lex_text(&mut tokens)?,
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    // Like `tokenize`, but instead of bailing on the first malformed
+    // construct, records the diagnostic and resynchronizes to keep
+    // tokenizing the rest of the input.
+    pub fn tokenize_recovering(&mut self) -> (Vec<Token>, Vec<ParserError>) {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+
+        while !self.eof() {
+            let result = match self.current_char() {
+                LESS_THAN => self.lex_tag(&mut tokens),
+                WHITESPACE => {
+                    self.skip_whitespace();
+                    Ok(())
+                }
+                _ => self.lex_text(&mut tokens),
+            };
+
+            if let Err(error) = result {
+                errors.push(error);
+                self.resync();
+            }
+        }
+
+        (tokens, errors)
+    }
+
+    // Resynchronizes after a lexing error by skipping ahead to the next
+    // `<` (a plausible tag start) or past the next `>` (a plausible tag
+    // end), always advancing by at least one byte so tokenizing is
+    // guaranteed to terminate.
+    fn resync(&mut self) {
+        if !self.eof() {
+            self.position += 1;
+        }
+
+        while !self.eof() && self.current_char() != LESS_THAN {
+            if self.current_char() == GREATER_THAN {
+                self.position += 1;
+                return;
+            }
+
+            self.position += 1;
+        }
+    }
+
+    fn lex_text(&mut self, tokens: &mut Vec<Token>) -> Result<(), ParserError> {
+        let start = self.position;
+
+        while !self.eof() && self.current_char() != LESS_THAN {
+            if self.current_char() == GREATER_THAN {
+                return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                    "text content",
+                    &(self.current_char() as char).to_string(),
+                    Span::new(self.position, self.position + 1),
+                )));
+            }
+
+            self.position += 1;
+        }
+
+        tokens.push(Token::Text(Span::new(start, self.position)));
+
+        Ok(())
+    }
+
+    fn lex_name(&mut self) -> Span {
+        let start = self.position;
+
+        while !self.eof()
+            && !matches!(
+                self.current_char(),
+                WHITESPACE | GREATER_THAN | SLASH | EQUALS
+            )
+        {
+            self.position += 1;
+        }
+
+        Span::new(start, self.position)
+    }
+
+    fn lex_attribute_value(&mut self) -> Result<Span, ParserError> {
+        let quote_start = self.position;
+
+        if self.eof() || self.current_char() != QUOTE {
+            return Err(ParserError::InvalidAttributeValue(
+                InvalidAttributeValueError::new(
+                    "",
+                    Span::new(quote_start, quote_start + 1),
+                ),
+            ));
+        }
+
+        self.position += 1;
+
+        while !self.eof() && self.current_char() != QUOTE {
+            self.position += 1;
+        }
+
+        if self.eof() {
+            return Err(ParserError::PrematureEndOfFile(
+                PrematureEndOfFileError::new(Span::new(quote_start, self.position)),
+            ));
+        }
+
+        let span = Span::new(quote_start, self.position + 1);
+        self.position += 1;
+
+        Ok(span)
+    }
+
+    fn lex_tag(&mut self, tokens: &mut Vec<Token>) -> Result<(), ParserError> {
+        let tag_start = self.position;
+        self.position += 1;
+
+        let closing = !self.eof() && self.current_char() == SLASH;
+        if closing {
+            self.position += 1;
+        }
+
+        let name = self.lex_name();
+        if name.start == name.end {
+            return Err(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
+                "",
+                Span::new(tag_start, self.position),
+            )));
+        }
+
+        let mut attribute_tokens = Vec::new();
+        let mut self_closing = false;
+
+        loop {
+            self.skip_whitespace();
+
+            if self.eof() {
+                return Err(ParserError::PrematureEndOfFile(
+                    PrematureEndOfFileError::new(Span::new(tag_start, self.position)),
+                ));
+            }
+
+            match self.current_char() {
+                GREATER_THAN => {
+                    self.position += 1;
+                    break;
+                }
+                SLASH => {
+                    self.position += 1;
+                    if self.eof() || self.current_char() != GREATER_THAN {
+                        return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                            ">",
+                            "/",
+                            Span::new(self.position - 1, self.position),
+                        )));
+                    }
+                    self.position += 1;
+                    self_closing = true;
+                    break;
+                }
+                _ => {
+                    let attribute_name = self.lex_name();
+                    attribute_tokens.push(Token::AttributeName(attribute_name));
+
+                    if !self.eof() && self.current_char() == EQUALS {
+                        self.position += 1;
+                        let value = self.lex_attribute_value()?;
+                        attribute_tokens.push(Token::AttributeValue(value));
+                    }
+                }
+            }
+        }
+
+        let boundary = if closing {
+            Token::TagClose { name }
+        } else if self_closing {
+            Token::TagSelfClose { name }
+        } else {
+            Token::TagOpen { name }
+        };
+
+        tokens.push(boundary);
+        tokens.extend(attribute_tokens);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_reports_tag_and_attribute_spans() {
+        let input = "<div id=\"a\">x</div>";
+        let tokens = Lexer::new(input).tokenize().unwrap();
+
+        let Token::TagOpen { name } = tokens[0] else {
+            panic!("expected a tag open token");
+        };
+        assert_eq!(&input[name.start..name.end], "div");
+
+        let Token::AttributeName(name) = tokens[1] else {
+            panic!("expected an attribute name token");
+        };
+        assert_eq!(&input[name.start..name.end], "id");
+
+        let Token::AttributeValue(value) = tokens[2] else {
+            panic!("expected an attribute value token");
+        };
+        assert_eq!(&input[value.start..value.end], "\"a\"");
+    }
+
+    #[test]
+    fn tokenize_rejects_unquoted_attribute_values() {
+        let error = Lexer::new("<div id=a>x</div>").tokenize().unwrap_err();
+
+        assert!(matches!(error, ParserError::InvalidAttributeValue(_)));
+    }
+
+    #[test]
+    fn tokenize_reports_self_closing_and_closing_tags() {
+        let tokens = Lexer::new("<br/><div></div>").tokenize().unwrap();
+
+        assert!(matches!(tokens[0], Token::TagSelfClose { .. }));
+        assert!(matches!(tokens[1], Token::TagOpen { .. }));
+        assert!(matches!(tokens[2], Token::TagClose { .. }));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod diagnostic;
+pub mod dom;
+pub mod entities;
+pub mod lexer;
+pub mod parser;
+pub mod span;
@@ -1,29 +1,48 @@
+use super::diagnostic::Diagnostic;
 use super::dom::{ElementAttributes, ElementChildren, ElementNode, Node, TextNode};
-
-const GREATER_THAN: u8 = 0x003E;
-const LESS_THAN: u8 = 0x003C;
-const WHITESPACE: u8 = 0x0020;
-const SLASH: u8 = 0x002F;
-const QUOTE: u8 = 0x0022;
+use super::entities;
+use super::lexer::{Lexer, Token};
+use super::span::Span;
 
 #[derive(Debug)]
 pub enum ParserError {
     UnexpectedToken(UnexpectedTokenError),
     PrematureEndOfFile(PrematureEndOfFileError),
-    Generic(GenericError),
     InvalidIdentifier(InvalidIdentifierError),
     InvalidAttributeValue(InvalidAttributeValueError),
 }
 
+impl ParserError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParserError::UnexpectedToken(error) => error.span,
+            ParserError::PrematureEndOfFile(error) => error.span,
+            ParserError::InvalidIdentifier(error) => error.span,
+            ParserError::InvalidAttributeValue(error) => error.span,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ParserError::UnexpectedToken(error) => &error.message,
+            ParserError::PrematureEndOfFile(error) => &error.message,
+            ParserError::InvalidIdentifier(error) => &error.identifier,
+            ParserError::InvalidAttributeValue(error) => &error.value,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct InvalidAttributeValueError {
     pub value: String,
+    pub span: Span,
 }
 
 impl InvalidAttributeValueError {
-    pub fn new(value: &str) -> InvalidAttributeValueError {
+    pub fn new(value: &str, span: Span) -> InvalidAttributeValueError {
         InvalidAttributeValueError {
             value: InvalidAttributeValueError::build_error_message(value),
+            span,
         }
     }
 
@@ -35,12 +54,14 @@ impl InvalidAttributeValueError {
 #[derive(Debug)]
 pub struct InvalidIdentifierError {
     pub identifier: String,
+    pub span: Span,
 }
 
 impl InvalidIdentifierError {
-    pub fn new(identifier: &str) -> InvalidIdentifierError {
+    pub fn new(identifier: &str, span: Span) -> InvalidIdentifierError {
         InvalidIdentifierError {
             identifier: InvalidIdentifierError::build_error_message(identifier),
+            span,
         }
     }
 
@@ -49,31 +70,18 @@ impl InvalidIdentifierError {
     }
 }
 
-#[derive(Debug)]
-pub struct GenericError {
-    pub position: usize,
-    pub message: String,
-}
-
-impl GenericError {
-    pub fn new(position: usize, message: &str) -> GenericError {
-        GenericError {
-            message: format!("{} at {}", message, position),
-            position,
-        }
-    }
-}
-
 #[derive(Debug)]
 pub struct PrematureEndOfFileError {
     pub position: usize,
+    pub span: Span,
     pub message: String,
 }
 
 impl PrematureEndOfFileError {
-    pub fn new(position: usize) -> PrematureEndOfFileError {
+    pub fn new(span: Span) -> PrematureEndOfFileError {
         PrematureEndOfFileError {
-            position,
+            position: span.start,
+            span,
             message: "Premature end of file".to_owned(),
         }
     }
@@ -82,14 +90,16 @@ impl PrematureEndOfFileError {
 #[derive(Debug)]
 pub struct UnexpectedTokenError {
     pub position: usize,
+    pub span: Span,
     pub message: String,
 }
 
 impl UnexpectedTokenError {
-    pub fn new(expected: &str, found: &str, position: usize) -> UnexpectedTokenError {
+    pub fn new(expected: &str, found: &str, span: Span) -> UnexpectedTokenError {
         UnexpectedTokenError {
-            message: UnexpectedTokenError::build_error_message(expected, found, position),
-            position,
+            message: UnexpectedTokenError::build_error_message(expected, found, span.start),
+            position: span.start,
+            span,
         }
     }
 
@@ -102,254 +112,409 @@ impl UnexpectedTokenError {
 }
 
 pub struct Parser {
-    input: Vec<u8>,
+    input: String,
+    tokens: Vec<Token>,
     position: usize,
 }
 
 impl Parser {
     pub fn new(input: &str) -> Parser {
         Parser {
-            input: input.as_bytes().into(),
+            input: input.to_owned(),
+            tokens: Vec::new(),
             position: 0,
         }
     }
 
-    fn current_char(&self) -> u8 {
-        self.input[self.position]
+    fn slice(&self, span: Span) -> &str {
+        &self.input[span.start..span.end]
     }
 
-    fn next_char(&self) -> Result<u8, ParserError> {
-        if self.position + 1 >= self.input.len() {
-            return Err(ParserError::Generic(GenericError::new(
-                self.position,
-                "index out of bounds",
-            )));
-        }
-
-        Ok(self.input[self.position + 1])
+    fn current_token(&self) -> Option<Token> {
+        self.tokens.get(self.position).copied()
     }
 
-    fn validate_identifier(tag_name: &str) -> bool {
-        for char in tag_name.chars() {
-            if !char.is_alphanumeric() {
-                return false;
-            }
-        }
+    fn eof_span(&self) -> Span {
+        Span::new(self.input.len(), self.input.len())
+    }
 
-        return true;
+    // Renders `error` as a caret-style diagnostic against the source this
+    // parser was constructed with.
+    pub fn render_error(&self, error: &ParserError) -> String {
+        Diagnostic::render(&self.input, error.span(), error.message())
     }
 
-    fn validate_attribute_value(value: &str) -> bool {
-        let chars = value.as_bytes();
+    // Identifiers must start with a letter (not a digit) and contain only
+    // alphanumeric characters after that.
+    fn validate_identifier(tag_name: &str) -> bool {
+        let mut chars = tag_name.chars();
 
-        if chars[0] != QUOTE || chars[value.len() - 1] != QUOTE {
-            return false;
+        match chars.next() {
+            Some(first) if first.is_alphabetic() => {}
+            _ => return false,
         }
 
-        true
+        chars.all(|char| char.is_alphanumeric())
     }
 
     fn strip_attribute_value_quotes(value: &str) -> String {
         let chars = value.as_bytes();
+        let unquoted = String::from_utf8(chars[1..chars.len() - 1].to_vec()).unwrap();
 
-        String::from_utf8(chars[1..chars.len() - 1].to_vec()).unwrap()
+        entities::decode(&unquoted)
     }
 
-    fn parse_attribute_section(section: &str) -> Result<(String, String), ParserError> {
-        match section.split_once("=") {
-            Some((key, value)) => {
-                if !Parser::validate_identifier(key) {
-                    return Err(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
-                        key,
-                    )));
-                }
+    fn parse_attributes(&mut self) -> Result<ElementAttributes, ParserError> {
+        let mut attributes = ElementAttributes::new();
 
-                if !Parser::validate_attribute_value(value) {
-                    return Err(ParserError::InvalidAttributeValue(
-                        InvalidAttributeValueError::new(value),
-                    ));
-                }
+        while let Some(Token::AttributeName(name_span)) = self.current_token() {
+            self.position += 1;
 
-                return Ok((key.to_owned(), Parser::strip_attribute_value_quotes(value)));
-            }
-            None => {
-                return Ok((section.to_owned(), "".to_owned()));
+            let key = self.slice(name_span).to_owned();
+            if !Parser::validate_identifier(&key) {
+                return Err(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
+                    &key, name_span,
+                )));
             }
-        }
-    }
 
-    fn parse_attributes(attributes_string: &str) -> Result<ElementAttributes, ParserError> {
-        let mut parsed_attributes = ElementAttributes::new();
-        let attribute_sections = attributes_string.split(" ");
+            let value = match self.current_token() {
+                Some(Token::AttributeValue(value_span)) => {
+                    self.position += 1;
 
-        for section in attribute_sections {
-            let (key, value) = Parser::parse_attribute_section(section)?;
+                    Parser::strip_attribute_value_quotes(self.slice(value_span))
+                }
+                _ => "".to_owned(),
+            };
 
-            if parsed_attributes.contains_key(&key) {
+            if attributes.contains_key(&key) {
                 continue;
             }
 
-            parsed_attributes.insert(key.to_owned(), value.to_owned());
+            attributes.insert(key, value);
         }
 
-        Ok(parsed_attributes)
+        Ok(attributes)
     }
 
-    fn get_tag_data(&mut self) -> Result<(String, ElementAttributes), ParserError> {
-        let mut tag = String::new();
-
-        while !self.eof() && self.current_char() != GREATER_THAN {
-            let current_char = self.current_char();
+    fn parse_opening_tag(&mut self) -> Result<(String, Span, ElementAttributes, bool), ParserError> {
+        let (name_span, self_closing) = match self.current_token() {
+            Some(Token::TagOpen { name }) => (name, false),
+            Some(Token::TagSelfClose { name }) => (name, true),
+            Some(token) => {
+                return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                    "tag open",
+                    token.describe(),
+                    token.span(),
+                )))
+            }
+            None => {
+                return Err(ParserError::PrematureEndOfFile(
+                    PrematureEndOfFileError::new(self.eof_span()),
+                ))
+            }
+        };
+        self.position += 1;
 
-            tag.push(current_char as char);
-            self.position += 1;
+        let tag_name = self.slice(name_span).to_owned();
+        if !Parser::validate_identifier(&tag_name) {
+            return Err(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
+                &tag_name, name_span,
+            )));
         }
 
-        // Reached eof before closing tag
-        if self.current_char() != GREATER_THAN {
-            return Err(ParserError::PrematureEndOfFile(
-                PrematureEndOfFileError::new(self.position),
-            ));
+        let attributes = self.parse_attributes()?;
+
+        Ok((tag_name, name_span, attributes, self_closing))
+    }
+
+    fn parse_element(&mut self) -> Result<Node, ParserError> {
+        let (tag_name, _, attributes, self_closing) = self.parse_opening_tag()?;
+
+        let mut node = ElementNode {
+            tag_name,
+            attributes,
+            children: ElementChildren::new(),
+        };
+
+        if !self_closing {
+            node.children = self.parse_children(&node)?;
         }
 
-        self.position += 1;
+        Ok(Node::Element(node))
+    }
 
-        let split_tag = tag.split_once(" ");
+    fn parse_children(&mut self, root: &ElementNode) -> Result<Vec<Node>, ParserError> {
+        let mut nodes = Vec::<Node>::new();
 
-        match split_tag {
-            Some((tag_name, attributes_string)) => {
-                if !Parser::validate_identifier(&tag_name) {
-                    return Err(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
-                        &tag,
-                    )));
+        loop {
+            match self.current_token() {
+                None => {
+                    return Err(ParserError::PrematureEndOfFile(
+                        PrematureEndOfFileError::new(self.eof_span()),
+                    ))
                 }
+                Some(Token::TagClose { name }) => {
+                    let closing_name = self.slice(name).to_owned();
+
+                    if closing_name != root.tag_name {
+                        return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                            &format!("</{}>", root.tag_name),
+                            &closing_name,
+                            name,
+                        )));
+                    }
 
-                let attributes = Parser::parse_attributes(attributes_string)?;
+                    self.position += 1;
 
-                return Ok((tag_name.to_owned(), attributes));
-            }
-            None => {
-                if !Parser::validate_identifier(&tag) {
-                    return Err(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
-                        &tag,
-                    )));
+                    return Ok(nodes);
+                }
+                Some(Token::TagOpen { .. }) | Some(Token::TagSelfClose { .. }) => {
+                    nodes.push(self.parse_element()?);
+                }
+                Some(Token::Text(span)) => {
+                    self.position += 1;
+
+                    nodes.push(Node::Text(TextNode {
+                        content: entities::decode(self.slice(span).trim()),
+                    }));
+                }
+                Some(token) => {
+                    return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                        "element or text",
+                        token.describe(),
+                        token.span(),
+                    )))
                 }
-                return Ok((tag, ElementAttributes::new()));
             }
         }
     }
 
-    fn eof(&self) -> bool {
-        self.position >= self.input.len()
-    }
+    pub fn parse(&mut self) -> Result<Node, ParserError> {
+        self.tokens = Lexer::new(&self.input).tokenize()?;
+        self.position = 0;
 
-    fn skip_whitespaces(&mut self) {
-        if self.eof() {
-            return;
+        match self.current_token() {
+            Some(Token::TagOpen { .. }) | Some(Token::TagSelfClose { .. }) => self.parse_element(),
+            Some(token) => Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                "<",
+                token.describe(),
+                token.span(),
+            ))),
+            None => Err(ParserError::PrematureEndOfFile(
+                PrematureEndOfFileError::new(self.eof_span()),
+            )),
         }
+    }
+
+    // Like `parse`, but instead of bailing on the first error, records
+    // every diagnostic it hits (from lexing and from tree building) and
+    // keeps going, returning as much of the tree as it could recover.
+    pub fn parse_recovering(&mut self) -> (Option<Node>, Vec<ParserError>) {
+        let (tokens, mut errors) = Lexer::new(&self.input).tokenize_recovering();
+        self.tokens = tokens;
+        self.position = 0;
+
+        let node = self.parse_element_recovering(&mut errors);
+
+        (node, errors)
+    }
+
+    // Skips ahead to the next tag-boundary token (`TagOpen`, `TagClose` or
+    // `TagSelfClose`), always advancing by at least one token so recovery
+    // is guaranteed to terminate.
+    fn skip_to_next_boundary(&mut self) {
+        self.position += 1;
+
+        while let Some(token) = self.current_token() {
+            if matches!(
+                token,
+                Token::TagOpen { .. } | Token::TagClose { .. } | Token::TagSelfClose { .. }
+            ) {
+                break;
+            }
 
-        if self.current_char() == WHITESPACE {
             self.position += 1;
-            self.skip_whitespaces();
         }
     }
 
-    fn get_text_content(&mut self) -> Result<String, ParserError> {
-        let mut content = Vec::<u8>::new();
+    fn parse_attributes_recovering(&mut self, errors: &mut Vec<ParserError>) -> ElementAttributes {
+        let mut attributes = ElementAttributes::new();
 
-        while !self.eof() && self.current_char() != LESS_THAN {
-            let current_char = self.current_char();
+        while let Some(Token::AttributeName(name_span)) = self.current_token() {
+            self.position += 1;
 
-            if self.current_char() == GREATER_THAN {
-                return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
-                    "text content",
-                    &(self.current_char() as char).to_string(),
-                    self.position,
+            let key = self.slice(name_span).to_owned();
+            let valid_key = Parser::validate_identifier(&key);
+            if !valid_key {
+                errors.push(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
+                    &key, name_span,
                 )));
             }
 
-            content.push(current_char);
+            let value = match self.current_token() {
+                Some(Token::AttributeValue(value_span)) => {
+                    self.position += 1;
+
+                    Parser::strip_attribute_value_quotes(self.slice(value_span))
+                }
+                _ => "".to_owned(),
+            };
 
-            self.position += 1;
+            if !valid_key || attributes.contains_key(&key) {
+                continue;
+            }
+
+            attributes.insert(key, value);
         }
 
-        Ok(String::from_utf8(content).unwrap())
+        attributes
     }
 
-    fn get_element_content(&mut self, root: &ElementNode) -> Result<Vec<Node>, ParserError> {
-        let mut nodes = Vec::<Node>::new();
-
-        while !self.eof() {
-            match self.current_char() {
-                LESS_THAN => {
-                    let next_character = self.next_char()?;
-
-                    if next_character == SLASH {
-                        self.position += 1;
-                    }
+    fn parse_element_recovering(&mut self, errors: &mut Vec<ParserError>) -> Option<Node> {
+        loop {
+            match self.current_token() {
+                Some(Token::TagOpen { name }) | Some(Token::TagSelfClose { name }) => {
+                    let self_closing = matches!(self.current_token(), Some(Token::TagSelfClose { .. }));
                     self.position += 1;
 
-                    let (tag_name, attributes) = self.get_tag_data()?;
-
-                    if next_character == SLASH {
-                        if tag_name != root.tag_name {
-                            return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
-                                &format!("</{}>", root.tag_name),
-                                &(self.current_char() as char).to_string(),
-                                self.position,
-                            )));
-                        } else {
-                            return Ok(nodes);
-                        }
+                    let tag_name = self.slice(name).to_owned();
+                    if !Parser::validate_identifier(&tag_name) {
+                        errors.push(ParserError::InvalidIdentifier(InvalidIdentifierError::new(
+                            &tag_name, name,
+                        )));
+                        self.skip_to_next_boundary();
+                        continue;
                     }
 
+                    let attributes = self.parse_attributes_recovering(errors);
+
                     let mut node = ElementNode {
                         tag_name,
                         attributes,
                         children: ElementChildren::new(),
                     };
 
-                    node.children = self.get_element_content(&node)?;
+                    if !self_closing {
+                        node.children = self.parse_children_recovering(&node, errors);
+                    }
+
+                    return Some(Node::Element(node));
+                }
+                Some(token) => {
+                    errors.push(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                        "tag open",
+                        token.describe(),
+                        token.span(),
+                    )));
+                    self.skip_to_next_boundary();
 
-                    nodes.push(Node::Element(node));
+                    if self.current_token().is_none() {
+                        return None;
+                    }
+                }
+                None => {
+                    errors.push(ParserError::PrematureEndOfFile(
+                        PrematureEndOfFileError::new(self.eof_span()),
+                    ));
+                    return None;
                 }
-                WHITESPACE => self.skip_whitespaces(),
-                // Text content
-                _ => nodes.push(Node::Text(TextNode {
-                    content: self.get_text_content()?.trim().to_owned(),
-                })),
             }
         }
-
-        Err(ParserError::PrematureEndOfFile(
-            PrematureEndOfFileError::new(self.position),
-        ))
     }
 
-    pub fn parse(&mut self) -> Result<Node, ParserError> {
-        let current_char = self.current_char();
+    fn parse_children_recovering(
+        &mut self,
+        root: &ElementNode,
+        errors: &mut Vec<ParserError>,
+    ) -> Vec<Node> {
+        let mut nodes = Vec::new();
+
+        loop {
+            match self.current_token() {
+                None => {
+                    errors.push(ParserError::PrematureEndOfFile(
+                        PrematureEndOfFileError::new(self.eof_span()),
+                    ));
+                    return nodes;
+                }
+                Some(Token::TagClose { name }) => {
+                    let closing_name = self.slice(name).to_owned();
 
-        if current_char != LESS_THAN {
-            return Err(ParserError::UnexpectedToken(UnexpectedTokenError::new(
-                "<",
-                &(current_char as char).to_string(),
-                self.position,
-            )));
+                    if closing_name == root.tag_name {
+                        self.position += 1;
+
+                        return nodes;
+                    }
+
+                    // Don't treat a mismatched close tag as if it legitimately
+                    // closed this element, which would hide the mismatch.
+                    // Record the diagnostic and resync instead, same as any
+                    // other unexpected token here. The resync skips the close
+                    // tag, so an ancestor it matches is then reported as
+                    // unclosed at end of input.
+                    errors.push(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                        &format!("</{}>", root.tag_name),
+                        &closing_name,
+                        name,
+                    )));
+                    self.skip_to_next_boundary();
+                }
+                Some(Token::TagOpen { .. }) | Some(Token::TagSelfClose { .. }) => {
+                    match self.parse_element_recovering(errors) {
+                        Some(node) => nodes.push(node),
+                        None => return nodes,
+                    }
+                }
+                Some(Token::Text(span)) => {
+                    self.position += 1;
+
+                    nodes.push(Node::Text(TextNode {
+                        content: entities::decode(self.slice(span).trim()),
+                    }));
+                }
+                Some(token) => {
+                    errors.push(ParserError::UnexpectedToken(UnexpectedTokenError::new(
+                        "element or text",
+                        token.describe(),
+                        token.span(),
+                    )));
+                    self.skip_to_next_boundary();
+                }
+            }
         }
+    }
+}
 
-        self.position += 1;
-        let (tag_name, attributes) = self.get_tag_data()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let mut root_node = ElementNode {
-            tag_name,
-            attributes,
-            children: ElementChildren::new(),
+    #[test]
+    fn parse_recovering_reports_mismatched_close_tag_instead_of_closing_with_it() {
+        let mut parser = Parser::new("<div><span>text</div>");
+        let (node, errors) = parser.parse_recovering();
+
+        let Some(Node::Element(div)) = node else {
+            panic!("expected a recovered root element");
+        };
+
+        assert_eq!(div.tag_name, "div");
+        assert_eq!(div.children.len(), 1);
+
+        let Node::Element(span) = &div.children[0] else {
+            panic!("expected span to be recovered as div's child");
         };
 
-        let children = self.get_element_content(&root_node)?;
+        assert_eq!(span.tag_name, "span");
+        assert!(errors.len() >= 2);
+    }
+
+    #[test]
+    fn parse_rejects_tag_name_starting_with_a_digit() {
+        let mut parser = Parser::new("<3div>x</3div>");
 
-        root_node.children = children;
+        let error = parser.parse().unwrap_err();
 
-        Ok(Node::Element(root_node))
+        assert!(matches!(error, ParserError::InvalidIdentifier(_)));
     }
 }
@@ -0,0 +1,55 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Site {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Site {
+    // Scans `input` up to `offset`, counting newlines to find the 1-based
+    // line and char-based column. Offsets past the end of `input` clamp to
+    // the last line.
+    pub fn resolve(input: &str, offset: usize) -> Site {
+        let offset = offset.min(input.len());
+        let (line_start, _) = Site::line_bounds(input, offset);
+
+        let line = input.as_bytes()[..line_start]
+            .iter()
+            .filter(|byte| **byte == b'\n')
+            .count()
+            + 1;
+        let column = input[line_start..offset].chars().count() + 1;
+
+        Site { line, column }
+    }
+
+    pub fn describe(input: &str, offset: usize) -> String {
+        let site = Site::resolve(input, offset);
+
+        format!("{}:{}", site.line, site.column)
+    }
+
+    // Returns the half-open byte range of the line containing `offset`,
+    // clamping offsets past the end of `input` to the last line.
+    pub fn line_bounds(input: &str, offset: usize) -> (usize, usize) {
+        let offset = offset.min(input.len());
+
+        let line_start = input[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |index| offset + index);
+
+        (line_start, line_end)
+    }
+}
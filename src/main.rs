@@ -5,9 +5,16 @@ mod html;
 fn main() -> Result<(), ParserError> {
     let mut parser = html::parser::Parser::new("<div>content</div>");
 
-    let result = parser.parse()?;
+    match parser.parse() {
+        Ok(result) => {
+            println!("{:?}", result);
 
-    println!("{:?}", result);
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("{}", parser.render_error(&error));
 
-    Ok(())
+            Err(error)
+        }
+    }
 }